@@ -0,0 +1,181 @@
+// Copyright (c) 2016 est31 <MTest31@outlook.com>
+// and contributors. All rights reserved.
+// Licensed under MIT license, or Apache 2 license,
+// at your option. Please see the LICENSE file
+// attached to this source distribution for details.
+
+/*!
+Archive extraction module
+*/
+
+use crate::{ExtractMode, TaError};
+use flate2::read::GzDecoder;
+use std::fs::{create_dir_all, File};
+use std::io::{copy, Read};
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, EntryType};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+/// Unpacks the archive at `archive_path` into `dest_dir`, stripping a
+/// single leading top-level path component the way common tarball-unpack
+/// helpers do, so fixtures land at predictable paths.
+pub fn extract_archive(archive_path: &str, mode: ExtractMode, dest_dir: &str) -> Result<(), TaError> {
+    match mode {
+        ExtractMode::None => Ok(()),
+        ExtractMode::Tar => unpack_tar(Archive::new(File::open(archive_path)?), dest_dir),
+        ExtractMode::TarGz => unpack_tar(
+            Archive::new(GzDecoder::new(File::open(archive_path)?)),
+            dest_dir,
+        ),
+        ExtractMode::TarXz => unpack_tar(
+            Archive::new(XzDecoder::new(File::open(archive_path)?)),
+            dest_dir,
+        ),
+        ExtractMode::Zip => unpack_zip(archive_path, dest_dir),
+    }
+}
+
+fn unpack_tar<R: Read>(mut archive: Archive<R>, dest_dir: &str) -> Result<(), TaError> {
+    create_dir_all(dest_dir)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // Symlink/hardlink entries can point outside `dest_dir`, and a
+        // later, innocuous-looking entry can then be written straight
+        // through such a link by the OS. Refuse them instead of
+        // unpacking, the same way `strip_top_level` refuses path
+        // components that could escape `dest_dir` directly.
+        match entry.header().entry_type() {
+            EntryType::Symlink | EntryType::Link => return Err(TaError::UnsafeTarEntry),
+            _ => {}
+        }
+        let path = entry.path()?.into_owned();
+        if let Some(stripped) = strip_top_level(&path) {
+            entry.unpack(Path::new(dest_dir).join(stripped))?;
+        }
+    }
+    Ok(())
+}
+
+fn unpack_zip(archive_path: &str, dest_dir: &str) -> Result<(), TaError> {
+    create_dir_all(dest_dir)?;
+    let mut archive = ZipArchive::new(File::open(archive_path)?).map_err(TaError::Zip)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(TaError::Zip)?;
+        let stripped = match strip_top_level(&entry.mangled_name()) {
+            Some(p) => p,
+            None => continue,
+        };
+        let out_path = Path::new(dest_dir).join(stripped);
+        if entry.is_dir() {
+            create_dir_all(&out_path)?;
+        } else {
+            if let Some(parent) = out_path.parent() {
+                create_dir_all(parent)?;
+            }
+            let mut out_file = File::create(&out_path)?;
+            copy(&mut entry, &mut out_file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Strips the first path component, e.g. `foo-1.0/src/lib.rs` -> `src/lib.rs`.
+/// Returns `None` for paths consisting only of that top-level component
+/// (the archive's own root directory entry, if it has one), and for any
+/// path whose remaining components could escape `dest_dir` (`..`, an
+/// absolute root, or a Windows prefix) — such entries are skipped rather
+/// than unpacked.
+fn strip_top_level(path: &Path) -> Option<PathBuf> {
+    let mut comps = path.components();
+    // The leading component is discarded as the archive's own top-level
+    // directory, so it must not itself be something that could escape
+    // `dest_dir` once the rest of the path is joined onto it.
+    match comps.next()? {
+        Component::ParentDir | Component::Prefix(_) => return None,
+        Component::Normal(_) | Component::RootDir | Component::CurDir => {}
+    }
+    let mut rest = PathBuf::new();
+    for comp in comps {
+        match comp {
+            Component::Normal(c) => rest.push(c),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if rest.as_os_str().is_empty() {
+        None
+    } else {
+        Some(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_the_top_level_component() {
+        assert_eq!(
+            strip_top_level(Path::new("foo-1.0/src/lib.rs")),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn top_level_only_path_strips_to_none() {
+        assert_eq!(strip_top_level(Path::new("foo-1.0")), None);
+    }
+
+    #[test]
+    fn rejects_parent_dir_components() {
+        assert_eq!(strip_top_level(Path::new("foo/../../etc/passwd")), None);
+    }
+
+    #[test]
+    fn strips_leading_root_dir_like_any_other_top_level_component() {
+        assert_eq!(
+            strip_top_level(Path::new("/foo/etc/passwd")),
+            Some(PathBuf::from("foo/etc/passwd"))
+        );
+    }
+
+    #[test]
+    fn rejects_a_leading_parent_dir_component() {
+        assert_eq!(strip_top_level(Path::new("../etc/passwd")), None);
+    }
+
+    #[test]
+    fn ignores_redundant_cur_dir_components() {
+        assert_eq!(
+            strip_top_level(Path::new("foo/./src/./lib.rs")),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn unpack_tar_rejects_symlink_entries() {
+        use std::io::Cursor;
+        use tar::{Builder, Header};
+
+        let mut builder = Builder::new(Vec::new());
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "foo-1.0/evil", "/tmp")
+            .unwrap();
+        let tar_bytes = builder.into_inner().unwrap();
+
+        let dest_dir = std::env::temp_dir().join(format!(
+            "test-assets-unpack-tar-rejects-symlinks-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dest_dir);
+
+        let result = unpack_tar(Archive::new(Cursor::new(tar_bytes)), dest_dir.to_str().unwrap());
+        assert!(matches!(result, Err(TaError::UnsafeTarEntry)));
+
+        let _ = std::fs::remove_dir_all(&dest_dir);
+    }
+}
@@ -8,7 +8,7 @@
 Hash list module
 */
 
-use crate::Sha256Hash;
+use crate::HashValue;
 use crate::TaError;
 use std::collections::HashMap;
 use std::{
@@ -17,14 +17,14 @@ use std::{
 };
 
 pub struct HashList {
-    name_to_hash_map: HashMap<String, Sha256Hash>,
+    name_to_hash_map: HashMap<String, HashValue>,
 }
 
 impl HashList {
     pub fn from_file(path: &str) -> Result<Self, TaError> {
         let rdr = File::open(path)?;
         let mut brdr = BufReader::new(rdr);
-        return Ok(HashList::from_reader(&mut brdr)?);
+        HashList::from_reader(&mut brdr)
     }
 
     pub fn from_reader<T: BufRead>(brdr: &mut T) -> Result<Self, TaError> {
@@ -39,14 +39,14 @@ impl HashList {
                 Some(v) => v,
                 None => continue,
             };
-            let hash = Sha256Hash::from_hex(hash_str).map_err(|_| TaError::BadHashFormat)?;
+            let hash = HashValue::from_hex(hash_str).map_err(|_| TaError::BadHashFormat)?;
             let name = match spi.next() {
                 Some(v) => v,
                 None => continue,
             };
             name_to_hash_map.insert(name.to_owned(), hash);
         }
-        return Ok(HashList { name_to_hash_map });
+        Ok(HashList { name_to_hash_map })
     }
 
     pub fn to_file(&self, path: &str) -> Result<(), TaError> {
@@ -57,22 +57,22 @@ impl HashList {
 
     pub fn to_writer<W: Write>(&self, bwrtr: &mut BufWriter<W>) -> Result<(), TaError> {
         for (name, hash) in &self.name_to_hash_map {
-            bwrtr.write(format!("{} {}\n", hash.to_hex(), name).as_bytes())?;
+            bwrtr.write_all(format!("{} {}\n", hash.to_hex(), name).as_bytes())?;
         }
         Ok(())
     }
 
     pub fn new() -> Self {
-        return HashList {
+        HashList {
             name_to_hash_map: HashMap::new(),
-        };
+        }
     }
 
-    pub fn get_hash<'a>(&'a self, filename: &str) -> Option<&'a Sha256Hash> {
+    pub fn get_hash<'a>(&'a self, filename: &str) -> Option<&'a HashValue> {
         self.name_to_hash_map.get(filename)
     }
 
-    pub fn add_entry(&mut self, filename: &str, hash: &Sha256Hash) {
+    pub fn add_entry(&mut self, filename: &str, hash: &HashValue) {
         self.name_to_hash_map
             .insert(filename.to_owned(), hash.clone());
     }
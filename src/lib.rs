@@ -18,23 +18,33 @@ out of VCS and don't make them bloat your repository.
 
 Usage example:
 
-```
-#[test]
+```no_run
+# use test_assets::{TestAssetDef, ExtractMode};
 fn some_awesome_test() {
     let asset_defs = [
         TestAssetDef {
             filename : format!("file_a.png"),
             hash : format!("<sha256 here>"),
             url : format!("https://url/to/a.png"),
+            extract: ExtractMode::None,
         },
         TestAssetDef {
             filename : format!("file_b.png"),
             hash : format!("<sha256 here>"),
             url : format!("https://url/to/a.png"),
+            extract: ExtractMode::None,
         },
     ];
-    test_assets::download_test_files(&asset_defs,
-        "test-assets", true).unwrap();
+    let outcomes = test_assets::download_test_files(&asset_defs,
+        "test-assets", test_assets::DEFAULT_MAX_DOWNLOAD_ATTEMPTS).unwrap();
+    for outcome in &outcomes {
+        match outcome {
+            test_assets::AssetOutcome::HashMismatch { .. } | test_assets::AssetOutcome::Failed { .. } => {
+                panic!("failed to fetch a test asset: {:?}", outcome);
+            }
+            _ => {}
+        }
+    }
     // use your files here
     // with path under test-assets/file_a.png and test-assets/file_b.png
 }
@@ -42,19 +52,39 @@ fn some_awesome_test() {
 
 If you have run the test once, it will re-use the files
 instead of re-downloading them.
+
+Progress is reported through the `log` crate rather than printed
+directly, so set up a logger (e.g. `env_logger`) if you want to see it.
 */
 
 extern crate curl;
+extern crate flate2;
+extern crate log;
+extern crate md5;
+extern crate sha1;
 extern crate sha2;
+extern crate sha3;
+extern crate tar;
+extern crate xz2;
+extern crate zip;
 
+mod extract;
 mod hash_list;
 
 use curl::easy::Easy;
+use extract::extract_archive;
 use hash_list::HashList;
 use sha2::digest::Digest;
-use sha2::Sha256;
-use std::fs::{create_dir_all, File};
-use std::io::{self, Write};
+use std::fs::{create_dir_all, remove_file, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Default number of attempts made to download and verify a single asset
+/// before giving up on it. Passed to
+/// [`download_test_files`] and [`download_test_files_verified`] as their
+/// `max_attempts` argument; pass a different value to make it configurable.
+pub const DEFAULT_MAX_DOWNLOAD_ATTEMPTS: u32 = 3;
 
 /// Definition for a test file
 ///
@@ -62,56 +92,208 @@ use std::io::{self, Write};
 pub struct TestAssetDef {
     /// Name of the file on disk. This should be unique for the file.
     pub filename: String,
-    /// Sha256 hash of the file's data in hexadecimal lowercase representation
+    /// Hash of the file's data in hexadecimal lowercase representation.
+    ///
+    /// Defaults to SHA-256 when given as a bare hex string, for backwards
+    /// compatibility. To use a different algorithm, prefix the hash with
+    /// its name and a colon, e.g. `"sha512:abcd..."`, `"sha1:abcd..."` or
+    /// `"md5:abcd..."`.
     pub hash: String,
     /// The url the test file can be obtained from
     pub url: String,
+    /// If the downloaded file is an archive, how to unpack it once its
+    /// hash has been verified. The archive is unpacked into a
+    /// subdirectory of the download directory, stripping its own
+    /// top-level directory component.
+    pub extract: ExtractMode,
+}
+
+/// How, if at all, a downloaded [`TestAssetDef`] should be unpacked
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExtractMode {
+    /// The file is not an archive and should be used as-is
+    None,
+    Tar,
+    TarGz,
+    TarXz,
+    Zip,
+}
+
+/// The hash algorithms supported for verifying test assets
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum HashAlgo {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Sha3_256,
+}
+
+impl HashAlgo {
+    fn prefix(self) -> &'static str {
+        match self {
+            HashAlgo::Md5 => "md5",
+            HashAlgo::Sha1 => "sha1",
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Sha512 => "sha512",
+            HashAlgo::Sha3_256 => "sha3-256",
+        }
+    }
+
+    fn from_prefix(s: &str) -> Option<Self> {
+        Some(match s {
+            "md5" => HashAlgo::Md5,
+            "sha1" => HashAlgo::Sha1,
+            "sha256" => HashAlgo::Sha256,
+            "sha512" => HashAlgo::Sha512,
+            "sha3-256" => HashAlgo::Sha3_256,
+            _ => return None,
+        })
+    }
+
+    fn byte_len(self) -> usize {
+        match self {
+            HashAlgo::Md5 => 16,
+            HashAlgo::Sha1 => 20,
+            HashAlgo::Sha256 => 32,
+            HashAlgo::Sha512 => 64,
+            HashAlgo::Sha3_256 => 32,
+        }
+    }
 }
 
-/// A type for a Sha256 hash value
+/// A hash value of one of the [`HashAlgo`] algorithms
 ///
 /// Provides conversion functionality to hex representation and back
-#[derive(PartialEq, Eq, Hash, Clone)]
-pub struct Sha256Hash([u8; 32]);
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum HashValue {
+    Md5([u8; 16]),
+    Sha1([u8; 20]),
+    Sha256([u8; 32]),
+    Sha512([u8; 64]),
+    Sha3_256([u8; 32]),
+}
 
-impl Sha256Hash {
-    pub fn from_digest(sha: Sha256) -> Self {
-        let sha = sha.finalize();
-        let bytes = sha[..].try_into().unwrap();
-        Sha256Hash(bytes)
+impl HashValue {
+    /// The algorithm this hash value was computed with
+    pub fn algo(&self) -> HashAlgo {
+        match *self {
+            HashValue::Md5(_) => HashAlgo::Md5,
+            HashValue::Sha1(_) => HashAlgo::Sha1,
+            HashValue::Sha256(_) => HashAlgo::Sha256,
+            HashValue::Sha512(_) => HashAlgo::Sha512,
+            HashValue::Sha3_256(_) => HashAlgo::Sha3_256,
+        }
     }
 
-    /// Converts the hexadecimal string to a hash value
-    pub fn from_hex(s: &str) -> Result<Self, ()> {
-        let mut res = Sha256Hash([0; 32]);
-        let mut idx = 0;
-        let mut iter = s.chars();
-        loop {
-            let upper = match iter.next().and_then(|c| c.to_digit(16)) {
-                Some(v) => v as u8,
-                None => return Err(()),
-            };
-            let lower = match iter.next().and_then(|c| c.to_digit(16)) {
-                Some(v) => v as u8,
-                None => return Err(()),
-            };
-            res.0[idx] = (upper << 4) | lower;
-            idx += 1;
-            if idx == 32 {
-                break;
-            }
+    fn bytes(&self) -> &[u8] {
+        match *self {
+            HashValue::Md5(ref b) => &b[..],
+            HashValue::Sha1(ref b) => &b[..],
+            HashValue::Sha256(ref b) => &b[..],
+            HashValue::Sha512(ref b) => &b[..],
+            HashValue::Sha3_256(ref b) => &b[..],
         }
-        return Ok(res);
     }
-    /// Converts the hash value to hexadecimal
+
+    fn from_bytes(algo: HashAlgo, bytes: &[u8]) -> Self {
+        match algo {
+            HashAlgo::Md5 => HashValue::Md5(bytes.try_into().unwrap()),
+            HashAlgo::Sha1 => HashValue::Sha1(bytes.try_into().unwrap()),
+            HashAlgo::Sha256 => HashValue::Sha256(bytes.try_into().unwrap()),
+            HashAlgo::Sha512 => HashValue::Sha512(bytes.try_into().unwrap()),
+            HashAlgo::Sha3_256 => HashValue::Sha3_256(bytes.try_into().unwrap()),
+        }
+    }
+
+    /// Parses a hash value, optionally prefixed with its algorithm name
+    /// and a colon (e.g. `sha512:abcd...`). Hash strings without a prefix
+    /// are assumed to be SHA-256, for backwards compatibility with hashes
+    /// predating multi-algorithm support.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_hex(s: &str) -> Result<Self, ()> {
+        let (algo, hex_str) = match s.find(':') {
+            Some(idx) => (HashAlgo::from_prefix(&s[..idx]).ok_or(())?, &s[idx + 1..]),
+            None => (HashAlgo::Sha256, s),
+        };
+        let bytes = hex_to_bytes(hex_str, algo.byte_len())?;
+        Ok(HashValue::from_bytes(algo, &bytes))
+    }
+
+    /// Converts the hash value to hexadecimal, prefixed with the algorithm
+    /// name and a colon, unless it is SHA-256, which is kept unprefixed for
+    /// backwards compatibility.
     pub fn to_hex(&self) -> String {
-        let mut res = String::with_capacity(64);
-        for v in self.0.iter() {
-            use std::char::from_digit;
-            res.push(from_digit(*v as u32 >> 4, 16).unwrap());
-            res.push(from_digit(*v as u32 & 15, 16).unwrap());
+        let hex = bytes_to_hex(self.bytes());
+        match self.algo() {
+            HashAlgo::Sha256 => hex,
+            algo => format!("{}:{}", algo.prefix(), hex),
+        }
+    }
+}
+
+fn hex_to_bytes(s: &str, len: usize) -> Result<Vec<u8>, ()> {
+    let mut res = vec![0u8; len];
+    let mut iter = s.chars();
+    for byte in res.iter_mut() {
+        let upper = iter.next().and_then(|c| c.to_digit(16)).ok_or(())? as u8;
+        let lower = iter.next().and_then(|c| c.to_digit(16)).ok_or(())? as u8;
+        *byte = (upper << 4) | lower;
+    }
+    if iter.next().is_some() {
+        return Err(());
+    }
+    Ok(res)
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    use std::char::from_digit;
+    let mut res = String::with_capacity(bytes.len() * 2);
+    for v in bytes.iter() {
+        res.push(from_digit(*v as u32 >> 4, 16).unwrap());
+        res.push(from_digit(*v as u32 & 15, 16).unwrap());
+    }
+    res
+}
+
+/// A hasher for one of the [`HashAlgo`] algorithms
+enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Sha3_256(sha3::Sha3_256),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgo::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgo::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            HashAlgo::Sha3_256 => Hasher::Sha3_256(sha3::Sha3_256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Sha3_256(h) => h.update(data),
+        }
+    }
+
+    fn finalize(self) -> HashValue {
+        match self {
+            Hasher::Md5(h) => HashValue::Md5(h.finalize().into()),
+            Hasher::Sha1(h) => HashValue::Sha1(h.finalize().into()),
+            Hasher::Sha256(h) => HashValue::Sha256(h.finalize().into()),
+            Hasher::Sha512(h) => HashValue::Sha512(h.finalize().into()),
+            Hasher::Sha3_256(h) => HashValue::Sha3_256(h.finalize().into()),
         }
-        return res;
     }
 }
 
@@ -119,8 +301,18 @@ impl Sha256Hash {
 pub enum TaError {
     Io(io::Error),
     Curl(curl::Error),
-    DownloadFailed(u32),
     BadHashFormat,
+    Zip(zip::result::ZipError),
+    /// `max_attempts` was 0, so a download could never be attempted.
+    InvalidMaxAttempts,
+    /// An archive entry was a symlink or hardlink, which could point
+    /// outside the extraction directory and have later entries written
+    /// straight through it.
+    UnsafeTarEntry,
+    /// A [`TestAssetDef`] asked to be extracted, but its filename doesn't
+    /// end in a recognized archive extension, so an extraction directory
+    /// distinct from the archive's own path couldn't be derived.
+    UnrecognizedArchiveExtension,
 }
 
 impl From<io::Error> for TaError {
@@ -135,44 +327,193 @@ impl From<curl::Error> for TaError {
     }
 }
 
+impl From<zip::result::ZipError> for TaError {
+    fn from(err: zip::result::ZipError) -> TaError {
+        TaError::Zip(err)
+    }
+}
+
 enum DownloadOutcome {
-    WithHash(Sha256Hash),
+    WithHash(HashValue),
     DownloadFailed(u32),
 }
 
+/// Downloads `tfile` into `dir`, writing each received chunk straight to
+/// disk and into the hasher as it arrives, so peak memory doesn't grow
+/// with the size of the asset.
 fn download_test_file(
     client: &mut Easy,
     tfile: &TestAssetDef,
     dir: &str,
+    algo: HashAlgo,
 ) -> Result<DownloadOutcome, TaError> {
     client.url(&tfile.url)?;
-    let mut content = Vec::new();
+
+    let path = format!("{}/{}", dir, tfile.filename);
+    let mut writer = BufWriter::new(File::create(&path)?);
+    let mut hasher = Hasher::new(algo);
+    let mut write_err = None;
 
     {
         let mut transfer = client.transfer();
         transfer.write_function(|data| {
-            content.extend_from_slice(data);
+            if let Err(e) = writer.write_all(data) {
+                write_err = Some(e);
+                return Ok(0);
+            }
+            hasher.update(data);
             Ok(data.len())
         })?;
-        transfer.perform()?;
+        if let Err(e) = transfer.perform() {
+            let _ = remove_file(&path);
+            return Err(e.into());
+        }
     }
-
-    let mut hasher = Sha256::new();
-    let mut file = File::create(format!("{}/{}", dir, tfile.filename))?;
-    file.write_all(&content)?;
-    hasher.update(&content);
+    if let Some(e) = write_err {
+        let _ = remove_file(&path);
+        return Err(TaError::Io(e));
+    }
+    writer.flush()?;
 
     let response_code = client.response_code()?;
-    if response_code < 200 || response_code > 399 {
+    if !(200..=399).contains(&response_code) {
+        let _ = remove_file(&path);
         return Ok(DownloadOutcome::DownloadFailed(response_code));
     }
-    return Ok(DownloadOutcome::WithHash(Sha256Hash::from_digest(
-        hasher,
-    )));
+    Ok(DownloadOutcome::WithHash(hasher.finalize()))
+}
+
+/// The final outcome of fetching and verifying a [`TestAssetDef`] from the
+/// network, once the attempts have succeeded or been used up.
+enum RetryOutcome {
+    WithHash(HashValue),
+    HashMismatch { expected: HashValue, found: HashValue },
+    DownloadFailed(u32),
+}
+
+/// A single failed attempt: either a [`RetryOutcome`] short of a matching
+/// hash, or a hard error (transport failure, local IO error) from
+/// `download_test_file` itself. Both are retryable; only the last one is
+/// surfaced once attempts are exhausted.
+enum AttemptFailure {
+    Outcome(RetryOutcome),
+    Err(TaError),
+}
+
+/// Downloads `tfile` into `dir`, making up to `max_attempts` attempts in
+/// total, with an increasing delay between them, if the transport fails, the server returns a
+/// non-2xx/3xx response code, or the downloaded data doesn't match
+/// `expected_hash`. `max_attempts` must be at least 1.
+fn download_test_file_retried(
+    client: &mut Easy,
+    tfile: &TestAssetDef,
+    dir: &str,
+    expected_hash: &HashValue,
+    max_attempts: u32,
+) -> Result<RetryOutcome, TaError> {
+    if max_attempts < 1 {
+        return Err(TaError::InvalidMaxAttempts);
+    }
+    let mut last_failure = None;
+    for attempt in 1..=max_attempts {
+        let failure = match download_test_file(client, tfile, dir, expected_hash.algo()) {
+            Ok(DownloadOutcome::WithHash(found_hash)) if &found_hash == expected_hash => {
+                return Ok(RetryOutcome::WithHash(found_hash))
+            }
+            Ok(DownloadOutcome::WithHash(found_hash)) => AttemptFailure::Outcome(RetryOutcome::HashMismatch {
+                expected: expected_hash.clone(),
+                found: found_hash,
+            }),
+            Ok(DownloadOutcome::DownloadFailed(code)) => {
+                AttemptFailure::Outcome(RetryOutcome::DownloadFailed(code))
+            }
+            Err(e) => AttemptFailure::Err(e),
+        };
+        log::warn!(
+            "attempt {}/{} for {} failed: {}",
+            attempt,
+            max_attempts,
+            tfile.filename,
+            match &failure {
+                AttemptFailure::Outcome(RetryOutcome::HashMismatch { expected, found }) => format!(
+                    "hash mismatch, expected {}, found {}",
+                    expected.to_hex(),
+                    found.to_hex()
+                ),
+                AttemptFailure::Outcome(RetryOutcome::DownloadFailed(code)) =>
+                    format!("download failed with code {}", code),
+                AttemptFailure::Outcome(RetryOutcome::WithHash(_)) => unreachable!(),
+                AttemptFailure::Err(e) => format!("{:?}", e),
+            }
+        );
+        last_failure = Some(failure);
+        if attempt < max_attempts {
+            sleep(Duration::from_millis(500 * attempt as u64));
+        }
+    }
+    match last_failure.unwrap() {
+        AttemptFailure::Outcome(outcome) => Ok(outcome),
+        AttemptFailure::Err(e) => Err(e),
+    }
 }
 
-/// Downloads the test files into the passed directory.
-pub fn download_test_files(defs: &[TestAssetDef], dir: &str, verbose: bool) -> Result<(), TaError> {
+/// The outcome of fetching a single [`TestAssetDef`], as returned by
+/// [`download_test_files`] and [`download_test_files_verified`].
+#[derive(Debug, Clone)]
+pub enum AssetOutcome {
+    /// The hash list already had a matching entry, so the download was
+    /// skipped without checking the file on disk.
+    Skipped,
+    /// The hash list already had a matching entry, and the file on disk
+    /// was re-hashed and found to still match it.
+    Verified,
+    /// The file was downloaded and its hash matched what was expected.
+    Downloaded { hash: HashValue },
+    /// The file was downloaded, but its hash never matched what was
+    /// expected, even after retrying.
+    HashMismatch { expected: HashValue, found: HashValue },
+    /// No attempt yielded a matching file, and the last attempt got a
+    /// non-2xx/3xx response code.
+    Failed { code: u32 },
+}
+
+/// Downloads the test files into the passed directory, making up to
+/// `max_attempts` attempts for each one (see [`DEFAULT_MAX_DOWNLOAD_ATTEMPTS`] for a
+/// sensible default).
+///
+/// If a file's hash is already present in the hash list, the download is
+/// skipped without checking whether the file on disk still matches it. To
+/// also re-verify files on disk, use [`download_test_files_verified`].
+///
+/// Progress and failures are reported through the `log` crate; set up a
+/// logger to see them.
+pub fn download_test_files(
+    defs: &[TestAssetDef],
+    dir: &str,
+    max_attempts: u32,
+) -> Result<Vec<AssetOutcome>, TaError> {
+    download_test_files_impl(defs, dir, false, max_attempts)
+}
+
+/// Like [`download_test_files`], but re-hashes the actual bytes on disk
+/// whenever the hash list claims a file is up to date, and re-downloads
+/// it if the file no longer matches. This catches files that were
+/// locally modified, truncated, or left partially written by an
+/// interrupted previous run.
+pub fn download_test_files_verified(
+    defs: &[TestAssetDef],
+    dir: &str,
+    max_attempts: u32,
+) -> Result<Vec<AssetOutcome>, TaError> {
+    download_test_files_impl(defs, dir, true, max_attempts)
+}
+
+fn download_test_files_impl(
+    defs: &[TestAssetDef],
+    dir: &str,
+    verify_on_disk: bool,
+    max_attempts: u32,
+) -> Result<Vec<AssetOutcome>, TaError> {
     let mut client = Easy::new();
     client.follow_location(true)?;
 
@@ -188,49 +529,171 @@ pub fn download_test_files(defs: &[TestAssetDef], dir: &str, verbose: bool) -> R
         }
     };
     create_dir_all(dir)?;
+    let mut outcomes = Vec::with_capacity(defs.len());
     for tfile in defs.iter() {
-        let tfile_hash = Sha256Hash::from_hex(&tfile.hash).map_err(|_| TaError::BadHashFormat)?;
-        if hash_list
+        let tfile_hash = HashValue::from_hex(&tfile.hash).map_err(|_| TaError::BadHashFormat)?;
+        let listed_hash_matches = hash_list
             .get_hash(&tfile.filename)
             .map(|h| h == &tfile_hash)
-            .unwrap_or(false)
-        {
-            // Hash match
-            if verbose {
-                println!(
+            .unwrap_or(false);
+        if listed_hash_matches {
+            if !verify_on_disk {
+                log::info!(
                     "File {} has matching hash inside hash list, skipping download",
                     tfile.filename
                 );
+                outcomes.push(AssetOutcome::Skipped);
+                continue;
             }
-            continue;
-        }
-        if verbose {
-            print!("Fetching file {} ...", tfile.filename);
-        }
-        let outcome = download_test_file(&mut client, tfile, dir)?;
-        use self::DownloadOutcome::*;
-        match &outcome {
-            &DownloadFailed(code) => return Err(TaError::DownloadFailed(code)),
-            &WithHash(ref hash) => hash_list.add_entry(&tfile.filename, hash),
-        }
-        if verbose {
-            print!("  => ");
-            match &outcome {
-                &DownloadFailed(code) => println!("Download failed with code {}", code),
-                &WithHash(ref found_hash) => {
-                    if found_hash == &tfile_hash {
-                        println!("Success")
-                    } else {
-                        println!(
-                            "Hash mismatch: found {}, expected {}",
-                            found_hash.to_hex(),
-                            tfile.hash
-                        )
-                    }
-                }
+            if file_matches_hash(&format!("{}/{}", dir, tfile.filename), &tfile_hash)? {
+                log::info!(
+                    "File {} has matching hash inside hash list and on disk, skipping download",
+                    tfile.filename
+                );
+                outcomes.push(AssetOutcome::Verified);
+                continue;
             }
+            log::warn!(
+                "File {} no longer matches its hash list entry, re-downloading",
+                tfile.filename
+            );
         }
+        log::info!("Fetching file {} ...", tfile.filename);
+        let outcome = match download_test_file_retried(&mut client, tfile, dir, &tfile_hash, max_attempts)? {
+            RetryOutcome::WithHash(hash) => {
+                if tfile.extract != ExtractMode::None {
+                    let dir_name = extract_dir_name(&tfile.filename)
+                        .ok_or(TaError::UnrecognizedArchiveExtension)?;
+                    let archive_path = format!("{}/{}", dir, tfile.filename);
+                    let extract_dir = format!("{}/{}", dir, dir_name);
+                    extract_archive(&archive_path, tfile.extract, &extract_dir)?;
+                }
+                hash_list.add_entry(&tfile.filename, &hash);
+                log::info!("  => Success");
+                AssetOutcome::Downloaded { hash }
+            }
+            RetryOutcome::HashMismatch { expected, found } => {
+                log::warn!("  => Hash mismatch: found {}, expected {}", found.to_hex(), expected.to_hex());
+                AssetOutcome::HashMismatch { expected, found }
+            }
+            RetryOutcome::DownloadFailed(code) => {
+                log::warn!("  => Download failed with code {}", code);
+                AssetOutcome::Failed { code }
+            }
+        };
+        outcomes.push(outcome);
     }
     hash_list.to_file(&hash_list_path)?;
-    Ok(())
+    Ok(outcomes)
+}
+
+/// Re-hashes the file at `path`, returning whether it matches
+/// `expected`. A missing file is treated as not matching, rather than
+/// as an error, so callers fall back to downloading it.
+fn file_matches_hash(path: &str, expected: &HashValue) -> Result<bool, TaError> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let mut reader = BufReader::new(file);
+    let mut hasher = Hasher::new(expected.algo());
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(&hasher.finalize() == expected)
+}
+
+/// The directory a downloaded archive is unpacked into: its filename with
+/// a known archive extension stripped. Returns `None` if the filename
+/// doesn't end in one of those extensions, so callers never extract into
+/// a directory that collides with the archive file's own path.
+fn extract_dir_name(filename: &str) -> Option<&str> {
+    for suffix in &[".tar.gz", ".tar.xz", ".tgz", ".txz", ".tar", ".zip"] {
+        if let Some(stripped) = filename.strip_suffix(suffix) {
+            return Some(stripped);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_value_bare_hex_defaults_to_sha256() {
+        let hex = "ab".repeat(32);
+        let hash = HashValue::from_hex(&hex).unwrap();
+        assert_eq!(hash.algo(), HashAlgo::Sha256);
+        // SHA-256 round-trips without a prefix, for backwards compatibility.
+        assert_eq!(hash.to_hex(), hex);
+    }
+
+    #[test]
+    fn hash_value_prefixed_hex_round_trips() {
+        let cases = [
+            (HashAlgo::Md5, "12".repeat(16)),
+            (HashAlgo::Sha1, "cd".repeat(20)),
+            (HashAlgo::Sha256, "ab".repeat(32)),
+            (HashAlgo::Sha512, "ef".repeat(64)),
+            (HashAlgo::Sha3_256, "34".repeat(32)),
+        ];
+        for (algo, hex) in cases {
+            let s = format!("{}:{}", algo.prefix(), hex);
+            let hash = HashValue::from_hex(&s).unwrap();
+            assert_eq!(hash.algo(), algo);
+            if algo == HashAlgo::Sha256 {
+                assert_eq!(hash.to_hex(), hex);
+            } else {
+                assert_eq!(hash.to_hex(), s);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_value_rejects_wrong_length_hex() {
+        assert!(HashValue::from_hex("sha256:abcd").is_err());
+        assert!(HashValue::from_hex(&"ab".repeat(31)).is_err());
+    }
+
+    #[test]
+    fn hash_value_rejects_unknown_prefix() {
+        assert!(HashValue::from_hex(&format!("crc32:{}", "ab".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn download_test_file_retried_rejects_zero_max_attempts() {
+        let tfile = TestAssetDef {
+            filename: "file.png".to_owned(),
+            hash: "ab".repeat(32),
+            url: "https://example.com/file.png".to_owned(),
+            extract: ExtractMode::None,
+        };
+        let hash = HashValue::from_hex(&tfile.hash).unwrap();
+        let mut client = Easy::new();
+        let result = download_test_file_retried(&mut client, &tfile, "dir", &hash, 0);
+        assert!(matches!(result, Err(TaError::InvalidMaxAttempts)));
+    }
+
+    #[test]
+    fn extract_dir_name_strips_known_archive_suffixes() {
+        assert_eq!(extract_dir_name("foo.tar.gz"), Some("foo"));
+        assert_eq!(extract_dir_name("foo.tar.xz"), Some("foo"));
+        assert_eq!(extract_dir_name("foo.tgz"), Some("foo"));
+        assert_eq!(extract_dir_name("foo.txz"), Some("foo"));
+        assert_eq!(extract_dir_name("foo.tar"), Some("foo"));
+        assert_eq!(extract_dir_name("foo.zip"), Some("foo"));
+    }
+
+    #[test]
+    fn extract_dir_name_rejects_unrecognized_extensions() {
+        assert_eq!(extract_dir_name("foo.png"), None);
+        assert_eq!(extract_dir_name("foo"), None);
+    }
 }